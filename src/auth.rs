@@ -0,0 +1,126 @@
+use crate::error::ApiError;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePool;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tide::{Middleware, Next, Request, StatusCode};
+
+const TOKEN_TTL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: i64,
+    pub username: String,
+    pub jti: String,
+    pub exp: usize,
+}
+
+/// Identity resolved from a validated bearer token, stashed in the request
+/// extensions by `AuthMiddleware` so handlers never have to trust a
+/// client-supplied `user_id` again.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthUser {
+    pub user_id: i64,
+}
+
+pub fn encode_token(user_id: i64, username: &str, secret: &str) -> tide::Result<String> {
+    let exp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + TOKEN_TTL_SECS;
+
+    let claims = Claims {
+        sub: user_id,
+        username: username.to_string(),
+        jti: uuid::Uuid::new_v4().to_string(),
+        exp: exp as usize,
+    };
+
+    let token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+    )?;
+
+    Ok(token)
+}
+
+pub fn decode_claims(token: &str, secret: &str) -> tide::Result<Claims> {
+    let data = jsonwebtoken::decode::<Claims>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
+        &jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256),
+    )
+    .map_err(|e| tide::Error::from_str(StatusCode::Unauthorized, e.to_string()))?;
+
+    Ok(data.claims)
+}
+
+/// Pulls the bearer token off a request and decodes it, without checking
+/// the revocation denylist. Used by `logout`, which needs the raw `jti`
+/// claim rather than a resolved `AuthUser`.
+///
+/// Accepts the token either as an `Authorization: Bearer` header or as a
+/// `?token=` query parameter, since the browser `WebSocket` API cannot set
+/// custom headers on the upgrade request.
+pub fn bearer_claims(req: &Request<AppState>) -> tide::Result<Claims> {
+    let token = req
+        .header("Authorization")
+        .and_then(|values| values.get(0))
+        .and_then(|value| value.as_str().strip_prefix("Bearer "))
+        .map(|token| token.to_string())
+        .or_else(|| {
+            req.url()
+                .query_pairs()
+                .find(|(key, _)| key == "token")
+                .map(|(_, value)| value.into_owned())
+        })
+        .ok_or_else(|| tide::Error::from_str(StatusCode::Unauthorized, "Token tidak ditemukan"))?;
+
+    decode_claims(&token, &req.state().jwt_secret)
+}
+
+/// Like `bearer_claims`, but returns `None` instead of an error when no
+/// token is present or it fails to validate. Used by routes (like
+/// `get_chats` and the chat WebSocket) where auth personalizes the
+/// response but isn't required to use them at all.
+pub fn optional_user(req: &Request<AppState>) -> Option<AuthUser> {
+    bearer_claims(req).ok().map(|claims| AuthUser {
+        user_id: claims.sub,
+    })
+}
+
+async fn is_revoked(pool: &SqlitePool, jti: &str) -> tide::Result<bool> {
+    let row = sqlx::query("SELECT 1 FROM revoked_tokens WHERE jti = ?")
+        .bind(jti)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.is_some())
+}
+
+/// Validates the bearer token on every request it guards and, on success,
+/// stores the resolved `AuthUser` in the request extensions for handlers to
+/// read back out.
+pub struct AuthMiddleware;
+
+#[tide::utils::async_trait]
+impl Middleware<AppState> for AuthMiddleware {
+    async fn handle(&self, mut req: Request<AppState>, next: Next<'_, AppState>) -> tide::Result {
+        let claims = match bearer_claims(&req) {
+            Ok(claims) => claims,
+            Err(_) => return Err(ApiError::Unauthorized("Token tidak valid".into()).into()),
+        };
+
+        if is_revoked(&req.state().pool, &claims.jti).await? {
+            return Err(ApiError::Unauthorized("Token sudah dicabut".into()).into());
+        }
+
+        req.set_ext(AuthUser {
+            user_id: claims.sub,
+        });
+
+        Ok(next.run(req).await)
+    }
+}
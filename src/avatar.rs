@@ -0,0 +1,59 @@
+use image::imageops::FilterType;
+use image::{GenericImageView, ImageFormat};
+
+/// Reject uploads larger than this before even trying to decode them.
+pub const MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+/// Reject images wider or taller than this; anything bigger is almost
+/// certainly not a profile picture and not worth resizing. The check runs
+/// on the decoded image, so it does not save the decode itself.
+const MAX_UPLOAD_DIMENSION: u32 = 4096;
+const THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(Debug)]
+pub enum AvatarError {
+    TooLarge,
+    TooManyPixels,
+    Decode(image::ImageError),
+    Encode(image::ImageError),
+}
+
+impl std::fmt::Display for AvatarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AvatarError::TooLarge => write!(f, "Ukuran gambar melebihi batas"),
+            AvatarError::TooManyPixels => write!(f, "Resolusi gambar terlalu besar"),
+            AvatarError::Decode(e) => write!(f, "Gagal membaca gambar: {e}"),
+            AvatarError::Encode(e) => write!(f, "Gagal menyimpan gambar: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AvatarError {}
+
+/// Decodes an uploaded avatar, rejects anything over the configured size
+/// or pixel limits, and re-encodes it as a normalized square PNG
+/// thumbnail. Re-encoding strips EXIF/metadata and caps how much disk
+/// space one avatar can use.
+pub fn make_thumbnail(bytes: &[u8]) -> Result<Vec<u8>, AvatarError> {
+    if bytes.len() > MAX_UPLOAD_BYTES {
+        return Err(AvatarError::TooLarge);
+    }
+
+    let decoded = image::load_from_memory(bytes).map_err(AvatarError::Decode)?;
+    let (width, height) = decoded.dimensions();
+    if width > MAX_UPLOAD_DIMENSION || height > MAX_UPLOAD_DIMENSION {
+        return Err(AvatarError::TooManyPixels);
+    }
+
+    let side = width.min(height);
+    let thumbnail = decoded
+        .crop_imm((width - side) / 2, (height - side) / 2, side, side)
+        .resize_exact(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Lanczos3);
+
+    let mut png = Vec::new();
+    thumbnail
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(AvatarError::Encode)?;
+
+    Ok(png)
+}
@@ -0,0 +1,101 @@
+use serde::Serialize;
+use tide::StatusCode;
+
+/// Uniform error type returned by every handler. Each variant maps to an
+/// HTTP status code; `ErrorMiddleware` renders whichever variant a handler
+/// returns as the same `{status, message}` JSON body, so API consumers
+/// never have to special-case an endpoint's error shape.
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound(String),
+    Unauthorized(String),
+    Validation(String),
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NotFound,
+            ApiError::Unauthorized(_) => StatusCode::Unauthorized,
+            ApiError::Validation(_) => StatusCode::BadRequest,
+            ApiError::Internal(_) => StatusCode::InternalServerError,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::NotFound(m)
+            | ApiError::Unauthorized(m)
+            | ApiError::Validation(m)
+            | ApiError::Internal(m) => m,
+        }
+    }
+
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody {
+            status: "error",
+            message: self.message().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ErrorBody {
+    status: &'static str,
+    message: String,
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        ApiError::Internal(err.to_string())
+    }
+}
+
+impl From<bcrypt::BcryptError> for ApiError {
+    fn from(err: bcrypt::BcryptError) -> Self {
+        ApiError::Internal(err.to_string())
+    }
+}
+
+/// Catches any error left on the response by a handler or middleware and
+/// re-renders it as the uniform JSON error body, in place of tide's default
+/// plain-text error rendering. An `ApiError` sets its own status code; any
+/// other error keeps the status tide gave it.
+pub struct ErrorMiddleware;
+
+#[tide::utils::async_trait]
+impl<State: Clone + Send + Sync + 'static> tide::Middleware<State> for ErrorMiddleware {
+    async fn handle(
+        &self,
+        req: tide::Request<State>,
+        next: tide::Next<'_, State>,
+    ) -> tide::Result {
+        let mut res = next.run(req).await;
+
+        if let Some(err) = res.error() {
+            let (status, body) = match err.downcast_ref::<ApiError>() {
+                Some(api_err) => (api_err.status(), api_err.body()),
+                None => (
+                    err.status(),
+                    ErrorBody {
+                        status: "error",
+                        message: err.to_string(),
+                    },
+                ),
+            };
+            res.set_status(status);
+            res.set_body(tide::Body::from_json(&body)?);
+        }
+
+        Ok(res)
+    }
+}
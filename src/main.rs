@@ -1,46 +1,66 @@
+mod auth;
+mod avatar;
+mod error;
+mod openapi;
+mod password;
+mod rooms;
+
+use error::ApiError;
 use serde::{Deserialize, Serialize};
 use sqlx::sqlite::SqlitePool;
 use sqlx::Row;
 use tide::http::headers::HeaderValue;
 use tide::security::CorsMiddleware;
 use tide::{Request, Response, StatusCode};
+use tide_websockets::{WebSocket, WebSocketConnection};
+use utoipa::OpenApi;
+use utoipa::ToSchema;
+
+const STATIC_DIR: &str = "static";
+const AVATAR_DIR: &str = "static/avatars";
+
+/// Shared application state: the DB pool, the in-memory per-movie
+/// WebSocket rooms, and the JWT signing secret, all read/established once
+/// at startup rather than re-fetched per request.
+#[derive(Clone)]
+struct AppState {
+    pool: SqlitePool,
+    rooms: rooms::RoomRegistry,
+    jwt_secret: std::sync::Arc<str>,
+}
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct LoginRequest {
     username: String,
     password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct LoginResponse {
     status: String,
     message: String,
+    token: String,
 }
 
-#[derive(Deserialize)]
-struct LogoutRequest {
-    username: String,
-}
-
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct LogoutResponse {
     status: String,
     message: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct RegisterRequest {
     username: String,
     password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct RegisterResponse {
     status: String,
     message: String,
 }
 
-#[derive(Serialize, sqlx::FromRow)]
+#[derive(Serialize, sqlx::FromRow, ToSchema)]
 struct Movie {
     id: i32,
     adult: bool,
@@ -62,29 +82,44 @@ struct Movie {
     vote_count: i32,
 }
 
-#[derive(Serialize, sqlx::FromRow)]
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
 struct ChatMessage {
     chat_id: i32,
     movie_id: i32,
     user_id: i64,
     username: String,
+    avatar_path: Option<String>,
     chat: String,
     created_at: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct SendChatRequest {
     movie_id: i32,
-    user_id: i64,
     chat: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ChatResponse {
     status: String,
     message: String,
 }
 
+const DEFAULT_CHAT_PAGE_LIMIT: i64 = 50;
+const MAX_CHAT_PAGE_LIMIT: i64 = 100;
+
+#[derive(Deserialize)]
+struct ChatPageQuery {
+    before: Option<i64>,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ChatPage {
+    chats: Vec<ChatMessage>,
+    next_cursor: Option<i32>,
+}
+
 #[async_std::main]
 async fn main() -> tide::Result<()> {
     let pool = SqlitePool::connect("sqlite:./movies.db").await?;
@@ -95,7 +130,7 @@ async fn main() -> tide::Result<()> {
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             username TEXT UNIQUE NOT NULL,
             password TEXT NOT NULL,
-            logged_in BOOLEAN NOT NULL DEFAULT 0
+            avatar_path TEXT
         )
         "#,
     )
@@ -118,19 +153,71 @@ async fn main() -> tide::Result<()> {
     .execute(&pool)
     .await?;
 
-    let mut app = tide::with_state(pool);
+    sqlx::query(
+        r#"
+            CREATE TABLE IF NOT EXISTS revoked_tokens (
+                jti TEXT PRIMARY KEY,
+                revoked_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+            CREATE TABLE IF NOT EXISTS blocks (
+                blocker_id INTEGER NOT NULL,
+                blocked_id INTEGER NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (blocker_id, blocked_id),
+                FOREIGN KEY (blocker_id) REFERENCES users(id),
+                FOREIGN KEY (blocked_id) REFERENCES users(id)
+            )
+            "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    let jwt_secret: std::sync::Arc<str> = std::env::var("JWT_SECRET")
+        .expect("JWT_SECRET env var must be set")
+        .into();
+
+    let state = AppState {
+        pool,
+        rooms: rooms::RoomRegistry::new(),
+        jwt_secret,
+    };
+
+    let mut app = tide::with_state(state);
+    app.with(error::ErrorMiddleware);
+
+    app.at("/openapi.json").get(openapi_json);
+
     app.at("/movies").get(get_movies);
 
     app.at("/register").post(register);
 
     app.at("/login").post(login);
 
-    app.at("/logout").post(logout);
+    app.at("/logout").with(auth::AuthMiddleware).post(logout);
 
-    app.at("/chat").post(post_chat);
+    app.at("/chat").with(auth::AuthMiddleware).post(post_chat);
+
+    app.at("/block").with(auth::AuthMiddleware).post(block_user);
+
+    app.at("/unblock")
+        .with(auth::AuthMiddleware)
+        .post(unblock_user);
+
+    app.at("/avatar").with(auth::AuthMiddleware).post(upload_avatar);
+
+    app.at("/static").serve_dir(STATIC_DIR)?;
 
     app.at("/chat/:movie_id").get(get_chats);
 
+    app.at("/chat/:movie_id/ws").get(WebSocket::new(chat_ws));
+
     println!("Server running at http://0.0.0.0:8081");
 
     let cors = CorsMiddleware::new()
@@ -144,9 +231,19 @@ async fn main() -> tide::Result<()> {
     Ok(())
 }
 
-async fn login(mut req: Request<SqlitePool>) -> tide::Result {
+#[utoipa::path(
+    post,
+    path = "/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 401, description = "Wrong password", body = error::ErrorBody),
+        (status = 404, description = "User not found", body = error::ErrorBody),
+    )
+)]
+async fn login(mut req: Request<AppState>) -> tide::Result<Response> {
     let data: LoginRequest = req.body_json().await?;
-    let pool = req.state();
+    let pool = &req.state().pool;
 
     let row = sqlx::query(
         r#"
@@ -157,63 +254,63 @@ async fn login(mut req: Request<SqlitePool>) -> tide::Result {
     )
     .bind(&data.username)
     .fetch_optional(pool)
-    .await?;
-
-    if row.is_none() {
-        let mut res = Response::new(StatusCode::Unauthorized);
-        res.set_body(tide::Body::from_json(&LoginResponse {
-            status: "Failed".into(),
-            message: "User tidak ditemukan".into(),
-        })?);
-        return Ok(res);
-    }
-
-    let row = row.unwrap();
+    .await?
+    .ok_or_else(|| ApiError::NotFound("User tidak ditemukan".into()))?;
 
     let user_id: i64 = row.try_get("id")?;
     let db_password: String = row.try_get("password")?;
 
-    if !bcrypt::verify(&data.password, &db_password)? {
-        let mut res = Response::new(StatusCode::Unauthorized);
-        res.set_body(tide::Body::from_json(&LoginResponse {
-            status: "Failed".into(),
-            message: "Password salah".into(),
-        })?);
-        return Ok(res);
+    // Old accounts are still hashed with bcrypt; verify against whichever
+    // algorithm produced the stored hash, and silently upgrade bcrypt
+    // accounts to Argon2id once they prove they know the password.
+    let verified = if db_password.starts_with("$2") {
+        let ok = bcrypt::verify(&data.password, &db_password)?;
+        if ok {
+            let upgraded = password::hash(&data.password);
+            sqlx::query("UPDATE users SET password = ? WHERE id = ?")
+                .bind(upgraded)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+        }
+        ok
+    } else {
+        password::verify(&data.password, &db_password)
+    };
+
+    if !verified {
+        return Err(ApiError::Unauthorized("Password salah".into()).into());
     }
 
-    sqlx::query("UPDATE users SET logged_in = 1 WHERE id = ?")
-        .bind(user_id)
-        .execute(pool)
-        .await?;
+    let token = auth::encode_token(user_id, &data.username, &req.state().jwt_secret)?;
 
     let mut res = Response::new(StatusCode::Ok);
     res.set_body(tide::Body::from_json(&LoginResponse {
         status: "success".into(),
         message: "Berhasil Login".into(),
+        token,
     })?);
 
     Ok(res)
 }
 
-async fn logout(mut req: Request<SqlitePool>) -> tide::Result {
-    let data: LogoutRequest = req.body_json().await?;
-    let pool = req.state();
+#[utoipa::path(
+    post,
+    path = "/logout",
+    responses(
+        (status = 200, description = "Logout successful", body = LogoutResponse),
+        (status = 401, description = "Missing or invalid token", body = error::ErrorBody),
+    )
+)]
+async fn logout(req: Request<AppState>) -> tide::Result<Response> {
+    let pool = &req.state().pool;
+    let claims = auth::bearer_claims(&req)?;
 
-    let result = sqlx::query("UPDATE users SET logged_in = 0 WHERE username = ?")
-        .bind(&data.username)
+    sqlx::query("INSERT OR IGNORE INTO revoked_tokens (jti) VALUES (?)")
+        .bind(&claims.jti)
         .execute(pool)
         .await?;
 
-    if result.rows_affected() == 0 {
-        let mut res = Response::new(StatusCode::BadRequest);
-        res.set_body(tide::Body::from_json(&LogoutResponse {
-            status: "error".into(),
-            message: "User tidak ditemukan".into(),
-        })?);
-        return Ok(res);
-    }
-
     let mut res = Response::new(StatusCode::Ok);
     res.set_body(tide::Body::from_json(&LogoutResponse {
         status: "success".into(),
@@ -223,46 +320,49 @@ async fn logout(mut req: Request<SqlitePool>) -> tide::Result {
     Ok(res)
 }
 
-async fn register(mut req: Request<SqlitePool>) -> tide::Result {
+#[utoipa::path(
+    post,
+    path = "/register",
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, description = "Registration successful", body = RegisterResponse),
+        (status = 400, description = "Username already taken", body = error::ErrorBody),
+    )
+)]
+async fn register(mut req: Request<AppState>) -> tide::Result<Response> {
     let data: RegisterRequest = req.body_json().await?;
 
-    let pool = req.state();
+    let pool = &req.state().pool;
 
-    let hashed = bcrypt::hash(data.password, bcrypt::DEFAULT_COST)?;
+    let hashed = password::hash(&data.password);
 
-    let result = sqlx::query(
+    sqlx::query(
         r#"
-        INSERT INTO users (username, password, logged_in)
-        VALUES (?, ?, 0)
+        INSERT INTO users (username, password)
+        VALUES (?, ?)
         "#,
     )
     .bind(data.username)
     .bind(hashed)
     .execute(pool)
-    .await;
-
-    match result {
-        Ok(_) => {
-            let mut res = Response::new(StatusCode::Ok);
-            res.set_body(tide::Body::from_json(&RegisterResponse {
-                status: "success".into(),
-                message: "Berhasil Daftar".into(),
-            })?);
-            Ok(res)
-        }
-        Err(e) => {
-            let mut res = Response::new(StatusCode::BadRequest);
-            res.set_body(tide::Body::from_json(&RegisterResponse {
-                status: "Failed".into(),
-                message: format!("Gagal Daftar: {}", e),
-            })?);
-            Ok(res)
-        }
-    }
+    .await
+    .map_err(|e| ApiError::Validation(format!("Gagal Daftar: {}", e)))?;
+
+    let mut res = Response::new(StatusCode::Ok);
+    res.set_body(tide::Body::from_json(&RegisterResponse {
+        status: "success".into(),
+        message: "Berhasil Daftar".into(),
+    })?);
+    Ok(res)
 }
 
-async fn get_movies(req: Request<SqlitePool>) -> tide::Result {
-    let pool = req.state();
+#[utoipa::path(
+    get,
+    path = "/movies",
+    responses((status = 200, description = "List of movies", body = [Movie]))
+)]
+async fn get_movies(req: Request<AppState>) -> tide::Result<Response> {
+    let pool = &req.state().pool;
 
     let movies: Vec<Movie> = sqlx::query_as::<_, Movie>(
         r#"
@@ -296,56 +396,142 @@ async fn get_movies(req: Request<SqlitePool>) -> tide::Result {
     Ok(res)
 }
 
-async fn post_chat(mut req: Request<SqlitePool>) -> tide::Result {
+#[utoipa::path(
+    post,
+    path = "/chat",
+    request_body = SendChatRequest,
+    responses(
+        (status = 200, description = "Message sent", body = ChatResponse),
+        (status = 400, description = "Empty message", body = error::ErrorBody),
+    )
+)]
+async fn post_chat(mut req: Request<AppState>) -> tide::Result<Response> {
+    let user_id = req
+        .ext::<auth::AuthUser>()
+        .expect("AuthMiddleware guards this route")
+        .user_id;
     let data: SendChatRequest = req.body_json().await?;
-    let pool = req.state();
+    let pool = &req.state().pool;
 
     if data.chat.trim().is_empty() {
-        let mut res = Response::new(StatusCode::BadRequest);
-        res.set_body(tide::Body::from_json(&ChatResponse {
-            status: "error".into(),
-            message: "Pesan chat tidak boleh kosong".into(),
-        })?);
-        return Ok(res);
+        return Err(ApiError::Validation("Pesan chat tidak boleh kosong".into()).into());
     }
 
-    let result = sqlx::query(
+    let inserted = sqlx::query(
         r#"
         INSERT INTO chats (movie_id, user_id, chat)
         VALUES (?, ?, ?)
         "#,
     )
     .bind(data.movie_id)
-    .bind(data.user_id)
+    .bind(user_id)
     .bind(&data.chat)
     .execute(pool)
-    .await;
-
-    match result {
-        Ok(_) => {
-            let mut res = Response::new(StatusCode::Ok);
-            res.set_body(tide::Body::from_json(&ChatResponse {
-                status: "success".into(),
-                message: "Pesan terkirim".into(),
-            })?);
-            Ok(res)
-        }
-        Err(e) => {
-            let mut res = Response::new(StatusCode::InternalServerError);
-            res.set_body(tide::Body::from_json(&ChatResponse {
-                status: "error".into(),
-                message: format!("Gagal mengirim pesan: {}", e),
-            })?);
-            Ok(res)
+    .await
+    .map_err(|e| ApiError::Internal(format!("Gagal mengirim pesan: {}", e)))?;
+
+    if let Ok(message) = fetch_chat_message(pool, inserted.last_insert_rowid()).await {
+        req.state().rooms.broadcast(data.movie_id, message);
+    }
+
+    let mut res = Response::new(StatusCode::Ok);
+    res.set_body(tide::Body::from_json(&ChatResponse {
+        status: "success".into(),
+        message: "Pesan terkirim".into(),
+    })?);
+    Ok(res)
+}
+
+async fn fetch_chat_message(pool: &SqlitePool, chat_id: i64) -> sqlx::Result<ChatMessage> {
+    sqlx::query_as::<_, ChatMessage>(
+        r#"
+        SELECT
+            c.chat_id,
+            c.movie_id,
+            c.user_id,
+            u.username,
+            u.avatar_path,
+            c.chat,
+            c.created_at
+        FROM chats c
+        JOIN users u ON c.user_id = u.id
+        WHERE c.chat_id = ?
+        "#,
+    )
+    .bind(chat_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Subscribes a client to a movie's live chat room and forwards every
+/// broadcast message to it as a JSON frame until it disconnects, hiding
+/// messages from anyone the viewer has blocked.
+async fn chat_ws(req: Request<AppState>, stream: WebSocketConnection) -> tide::Result<()> {
+    let movie_id: i32 = req.param("movie_id")?.parse().unwrap_or(0);
+    let state = req.state().clone();
+
+    let blocked = match auth::optional_user(&req) {
+        Some(viewer) => fetch_blocked_ids(&state.pool, viewer.user_id).await?,
+        None => Vec::new(),
+    };
+
+    let mut receiver = state.rooms.subscribe(movie_id);
+
+    loop {
+        match receiver.recv().await {
+            Ok(message) => {
+                if blocked.contains(&message.user_id) {
+                    continue;
+                }
+                if stream.send_json(&message).await.is_err() {
+                    break;
+                }
+            }
+            // We fell behind the room's 100-message buffer; resync by
+            // picking up the next message instead of treating it as a
+            // disconnect.
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
         }
     }
+
+    drop(receiver);
+    state.rooms.drop_if_empty(movie_id);
+    Ok(())
+}
+
+async fn fetch_blocked_ids(pool: &SqlitePool, viewer_id: i64) -> sqlx::Result<Vec<i64>> {
+    let rows = sqlx::query("SELECT blocked_id FROM blocks WHERE blocker_id = ?")
+        .bind(viewer_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(|row| row.try_get("blocked_id")).collect()
 }
 
-async fn get_chats(req: Request<SqlitePool>) -> tide::Result {
-    let pool = req.state();
+#[utoipa::path(
+    get,
+    path = "/chat/{movie_id}",
+    params(
+        ("movie_id" = i32, Path, description = "Movie to fetch chat history for"),
+        ("before" = Option<i64>, Query, description = "Only return messages older than this chat_id cursor"),
+        ("limit" = Option<i64>, Query, description = "Page size, clamped to 1..=100 (default 50)"),
+    ),
+    responses((status = 200, description = "Page of chat messages, newest first", body = ChatPage))
+)]
+async fn get_chats(req: Request<AppState>) -> tide::Result<Response> {
+    let pool = &req.state().pool;
 
     let movie_id_param = req.param("movie_id")?;
     let movie_id: i32 = movie_id_param.parse().unwrap_or(0);
+    let viewer_id = auth::optional_user(&req).map(|user| user.user_id).unwrap_or(-1);
+
+    let query: ChatPageQuery = req.query()?;
+    let before = query.before.unwrap_or(i64::MAX);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_CHAT_PAGE_LIMIT)
+        .clamp(1, MAX_CHAT_PAGE_LIMIT);
 
     let chats: Vec<ChatMessage> = sqlx::query_as::<_, ChatMessage>(
         r#"
@@ -354,19 +540,247 @@ async fn get_chats(req: Request<SqlitePool>) -> tide::Result {
             c.movie_id,
             c.user_id,
             u.username,
+            u.avatar_path,
             c.chat,
             c.created_at
         FROM chats c
         JOIN users u ON c.user_id = u.id
-        WHERE c.movie_id = ?
-        ORDER BY c.created_at ASC
+        LEFT JOIN blocks b ON c.user_id = b.blocked_id AND b.blocker_id = ?
+        WHERE c.movie_id = ? AND c.chat_id < ? AND b.blocked_id IS NULL
+        ORDER BY c.chat_id DESC
+        LIMIT ?
         "#,
     )
+    .bind(viewer_id)
     .bind(movie_id)
+    .bind(before)
+    .bind(limit)
     .fetch_all(pool)
     .await?;
 
+    let next_cursor = chats.last().map(|chat| chat.chat_id);
+
+    let mut res = Response::new(StatusCode::Ok);
+    res.set_body(tide::Body::from_json(&ChatPage { chats, next_cursor })?);
+    Ok(res)
+}
+
+#[derive(Deserialize, ToSchema)]
+struct BlockRequest {
+    username: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct BlockResponse {
+    status: String,
+    message: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/block",
+    request_body = BlockRequest,
+    responses(
+        (status = 200, description = "User blocked", body = BlockResponse),
+        (status = 404, description = "Target user not found", body = error::ErrorBody),
+    )
+)]
+async fn block_user(mut req: Request<AppState>) -> tide::Result<Response> {
+    let blocker_id = req
+        .ext::<auth::AuthUser>()
+        .expect("AuthMiddleware guards this route")
+        .user_id;
+    let data: BlockRequest = req.body_json().await?;
+    let pool = &req.state().pool;
+
+    let blocked_id = lookup_user_id(pool, &data.username).await?;
+
+    sqlx::query("INSERT OR IGNORE INTO blocks (blocker_id, blocked_id) VALUES (?, ?)")
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .execute(pool)
+        .await?;
+
+    let mut res = Response::new(StatusCode::Ok);
+    res.set_body(tide::Body::from_json(&BlockResponse {
+        status: "success".into(),
+        message: "User diblokir".into(),
+    })?);
+    Ok(res)
+}
+
+#[utoipa::path(
+    post,
+    path = "/unblock",
+    request_body = BlockRequest,
+    responses(
+        (status = 200, description = "Block removed", body = BlockResponse),
+        (status = 404, description = "Target user not found", body = error::ErrorBody),
+    )
+)]
+async fn unblock_user(mut req: Request<AppState>) -> tide::Result<Response> {
+    let blocker_id = req
+        .ext::<auth::AuthUser>()
+        .expect("AuthMiddleware guards this route")
+        .user_id;
+    let data: BlockRequest = req.body_json().await?;
+    let pool = &req.state().pool;
+
+    let blocked_id = lookup_user_id(pool, &data.username).await?;
+
+    sqlx::query("DELETE FROM blocks WHERE blocker_id = ? AND blocked_id = ?")
+        .bind(blocker_id)
+        .bind(blocked_id)
+        .execute(pool)
+        .await?;
+
+    let mut res = Response::new(StatusCode::Ok);
+    res.set_body(tide::Body::from_json(&BlockResponse {
+        status: "success".into(),
+        message: "Blokir dibatalkan".into(),
+    })?);
+    Ok(res)
+}
+
+async fn lookup_user_id(pool: &SqlitePool, username: &str) -> Result<i64, ApiError> {
+    sqlx::query("SELECT id FROM users WHERE username = ?")
+        .bind(username)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("User tidak ditemukan".into()))?
+        .try_get("id")
+        .map_err(ApiError::from)
+}
+
+#[derive(Serialize, ToSchema)]
+struct AvatarResponse {
+    status: String,
+    message: String,
+    avatar_path: String,
+}
+
+const BODY_READ_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Reads a request body into memory in fixed-size chunks, bailing out as
+/// soon as the running total crosses `limit` instead of buffering the full
+/// body first. Bounds memory use for an upload whose `Content-Length` is
+/// absent or understated.
+async fn read_capped_body(
+    req: &mut Request<AppState>,
+    limit: usize,
+) -> Result<bytes::Bytes, ApiError> {
+    use futures::AsyncReadExt;
+
+    let mut body = req.take_body();
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; BODY_READ_CHUNK_BYTES];
+
+    loop {
+        let n = body
+            .read(&mut chunk)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        if n == 0 {
+            break;
+        }
+        if buf.len() + n > limit {
+            return Err(ApiError::Validation("Ukuran unggahan melebihi batas".into()));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    Ok(bytes::Bytes::from(buf))
+}
+
+/// Expects a `multipart/form-data` body with the image in an `avatar` field.
+#[utoipa::path(
+    post,
+    path = "/avatar",
+    responses(
+        (status = 200, description = "Avatar uploaded", body = AvatarResponse),
+        (status = 400, description = "Missing, oversized, or undecodable image", body = error::ErrorBody),
+    )
+)]
+async fn upload_avatar(mut req: Request<AppState>) -> tide::Result<Response> {
+    let user_id = req
+        .ext::<auth::AuthUser>()
+        .expect("AuthMiddleware guards this route")
+        .user_id;
+
+    let boundary = req
+        .content_type()
+        .and_then(|content_type| content_type.param("boundary").map(|b| b.to_string()))
+        .ok_or_else(|| ApiError::Validation("Content-Type multipart tidak valid".into()))?;
+
+    // Reject an oversized upload off its declared Content-Length before
+    // reading anything, and re-check while streaming the body in below so a
+    // request that omits (or lies about) Content-Length still can't buffer
+    // more than the cap in memory.
+    if let Some(len) = req.len() {
+        if len > avatar::MAX_UPLOAD_BYTES {
+            return Err(ApiError::Validation("Ukuran unggahan melebihi batas".into()).into());
+        }
+    }
+
+    let body = read_capped_body(&mut req, avatar::MAX_UPLOAD_BYTES).await?;
+    let mut multipart = multer::Multipart::new(
+        futures::stream::once(async move { Ok::<_, std::io::Error>(body) }),
+        boundary,
+    );
+
+    let mut upload: Option<bytes::Bytes> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::Validation(e.to_string()))?
+    {
+        if field.name() == Some("avatar") {
+            upload = Some(
+                field
+                    .bytes()
+                    .await
+                    .map_err(|e| ApiError::Validation(e.to_string()))?,
+            );
+        }
+    }
+
+    let upload =
+        upload.ok_or_else(|| ApiError::Validation("File avatar tidak ditemukan".into()))?;
+
+    let thumbnail =
+        avatar::make_thumbnail(&upload).map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    async_std::fs::create_dir_all(AVATAR_DIR)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let filename = format!("{}.png", user_id);
+    async_std::fs::write(format!("{}/{}", AVATAR_DIR, filename), &thumbnail)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let avatar_path = format!("/{}/avatars/{}", STATIC_DIR, filename);
+    sqlx::query("UPDATE users SET avatar_path = ? WHERE id = ?")
+        .bind(&avatar_path)
+        .bind(user_id)
+        .execute(&req.state().pool)
+        .await?;
+
+    let mut res = Response::new(StatusCode::Ok);
+    res.set_body(tide::Body::from_json(&AvatarResponse {
+        status: "success".into(),
+        message: "Avatar berhasil diunggah".into(),
+        avatar_path,
+    })?);
+    Ok(res)
+}
+
+async fn openapi_json(_req: Request<AppState>) -> tide::Result<Response> {
+    let document = openapi::ApiDoc::openapi()
+        .to_json()
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
     let mut res = Response::new(StatusCode::Ok);
-    res.set_body(tide::Body::from_json(&chats)?);
+    res.set_content_type(tide::http::mime::JSON);
+    res.set_body(document);
     Ok(res)
 }
@@ -0,0 +1,40 @@
+use crate::{
+    error, AvatarResponse, BlockRequest, BlockResponse, ChatMessage, ChatPage, ChatResponse,
+    LoginRequest, LoginResponse, LogoutResponse, Movie, RegisterRequest, RegisterResponse,
+    SendChatRequest,
+};
+use utoipa::OpenApi;
+
+/// Aggregates the `#[utoipa::path]` annotations scattered across the
+/// handlers into one OpenAPI 3 document, served at `GET /openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::login,
+        crate::register,
+        crate::get_movies,
+        crate::post_chat,
+        crate::logout,
+        crate::get_chats,
+        crate::block_user,
+        crate::unblock_user,
+        crate::upload_avatar,
+    ),
+    components(schemas(
+        LoginRequest,
+        LoginResponse,
+        LogoutResponse,
+        RegisterRequest,
+        RegisterResponse,
+        Movie,
+        ChatMessage,
+        SendChatRequest,
+        ChatResponse,
+        ChatPage,
+        BlockRequest,
+        BlockResponse,
+        AvatarResponse,
+        error::ErrorBody,
+    ))
+)]
+pub struct ApiDoc;
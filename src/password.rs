@@ -0,0 +1,47 @@
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+/// Hashes a plaintext password with Argon2id, using a fresh random salt.
+pub fn hash(plaintext: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(plaintext.as_bytes(), &salt)
+        .expect("argon2 hashing a non-empty password cannot fail")
+        .to_string()
+}
+
+/// Verifies a plaintext password against an Argon2id hash produced by `hash`.
+pub fn verify(plaintext: &str, stored: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(stored) else {
+        return false;
+    };
+
+    Argon2::default()
+        .verify_password(plaintext.as_bytes(), &parsed)
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_a_matching_password() {
+        let hashed = hash("correct horse battery staple");
+        assert!(verify("correct horse battery staple", &hashed));
+    }
+
+    #[test]
+    fn rejects_a_wrong_password() {
+        let hashed = hash("correct horse battery staple");
+        assert!(!verify("incorrect horse battery staple", &hashed));
+    }
+
+    #[test]
+    fn same_password_hashes_differently_each_time() {
+        let a = hash("correct horse battery staple");
+        let b = hash("correct horse battery staple");
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::ChatMessage;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Per-room backlog kept in memory so a slow subscriber can fall a little
+/// behind without being disconnected outright.
+const ROOM_CAPACITY: usize = 100;
+
+/// Live WebSocket rooms, one `broadcast` channel per `movie_id`, created
+/// lazily on first subscriber and torn down once empty.
+#[derive(Clone, Default)]
+pub struct RoomRegistry {
+    rooms: Arc<Mutex<HashMap<i32, broadcast::Sender<ChatMessage>>>>,
+}
+
+impl RoomRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes while holding the lock, so a concurrent `drop_if_empty`
+    /// cannot remove the room between creating and joining it.
+    pub fn subscribe(&self, movie_id: i32) -> broadcast::Receiver<ChatMessage> {
+        let mut rooms = self.rooms.lock().unwrap();
+        rooms
+            .entry(movie_id)
+            .or_insert_with(|| broadcast::channel(ROOM_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Publishes to a room's subscribers. Having nobody subscribed yet is
+    /// not an error, the message is simply dropped and no room is created.
+    pub fn broadcast(&self, movie_id: i32, message: ChatMessage) {
+        let rooms = self.rooms.lock().unwrap();
+        if let Some(sender) = rooms.get(&movie_id) {
+            let _ = sender.send(message);
+        }
+    }
+
+    /// Removes a room once its last subscriber has disconnected, so dead
+    /// movie rooms don't accumulate in the map forever.
+    pub fn drop_if_empty(&self, movie_id: i32) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if let Some(sender) = rooms.get(&movie_id) {
+            if sender.receiver_count() == 0 {
+                rooms.remove(&movie_id);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn room_count(&self) -> usize {
+        self.rooms.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_the_room_once_the_last_subscriber_is_gone() {
+        let registry = RoomRegistry::new();
+        let receiver = registry.subscribe(1);
+        assert_eq!(registry.room_count(), 1);
+
+        drop(receiver);
+        registry.drop_if_empty(1);
+
+        assert_eq!(registry.room_count(), 0);
+    }
+
+    #[test]
+    fn keeps_the_room_while_a_subscriber_remains() {
+        let registry = RoomRegistry::new();
+        let _first = registry.subscribe(1);
+        let second = registry.subscribe(1);
+
+        drop(second);
+        registry.drop_if_empty(1);
+
+        assert_eq!(registry.room_count(), 1);
+    }
+}